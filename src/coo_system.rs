@@ -1,7 +1,11 @@
+use crate::epoch::{Epoch, EpochFormat};
 use crate::error::Error;
 use crate::header::WCSHeader;
 use crate::utils;
 
+use mapproj::LonLat;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RadeSys {
     /// International Celestial Reference System
     ICRS,
@@ -30,35 +34,52 @@ impl RadeSys {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CooSystem {
     EQUATORIAL,
     GALACTIC,
-    ECLIPTIC,
-    HELIOECLIPTIC,
+    /// Mean ecliptic of the given equinox.
+    ECLIPTIC { equinox: Epoch },
+    /// Mean ecliptic of the given equinox, heliocentric (same rotation as
+    /// [`CooSystem::ECLIPTIC`]; the two differ only in the origin of the
+    /// position, which this crate does not otherwise model).
+    HELIOECLIPTIC { equinox: Epoch },
     SUPERGALACTIC,
-    CUSTOM { radesys: RadeSys, equinox: f64 },
+    CUSTOM {
+        radesys: RadeSys,
+        equinox: Epoch,
+        /// The epoch of observation (`DATE-OBS`/`MJD-OBS`), needed to reduce
+        /// a `GAPPT` apparent place to a mean frame.
+        obs_epoch: Option<Epoch>,
+    },
 }
 
 impl CooSystem {
     pub fn parse(header: &WCSHeader) -> Result<Self, Error> {
-        // wrap get_float("EQUINOX") in a Result<f64, Error>
-        let equinox = match header.get_float("EQUINOX") {
-            Some(Ok(equinox)) => Ok(equinox),
-            _ => Err(Error::MandatoryWCSKeywordsMissing("EQUINOX")),
-        };
-
         let radesys = RadeSys::parse(header);
 
-        let coo_system = if let (Ok(radesys), Ok(equinox)) = (radesys, equinox) {
-            // if there is a radesys take it into account
-            CooSystem::CUSTOM { radesys, equinox }
+        let coo_system = if let Ok(radesys) = radesys {
+            // if there is a radesys take it into account, defaulting the
+            // equinox to B1950/J2000 when the header does not give one
+            let equinox = Epoch::parse_equinox(header, &radesys)?;
+            let obs_epoch = Epoch::parse_obs_epoch(header);
+
+            CooSystem::CUSTOM {
+                radesys,
+                equinox,
+                obs_epoch,
+            }
         } else {
             let ctype1 = header.get_ctype(1)?;
 
             match ctype1.as_bytes()[0] {
                 b'G' => CooSystem::GALACTIC,
-                b'E' => CooSystem::ECLIPTIC,
-                b'H' => CooSystem::HELIOECLIPTIC,
+                b'E' => CooSystem::ECLIPTIC {
+                    equinox: parse_ecliptic_equinox(header),
+                },
+                b'H' => CooSystem::HELIOECLIPTIC {
+                    equinox: parse_ecliptic_equinox(header),
+                },
                 b'S' => CooSystem::SUPERGALACTIC,
                 _ => CooSystem::EQUATORIAL,
             }
@@ -67,3 +88,430 @@ impl CooSystem {
         Ok(coo_system)
     }
 }
+
+/// Parses the `EQUINOX` (or legacy `EPOCH`) card for an ecliptic system,
+/// defaulting to J2000 when absent. Unlike [`Epoch::parse_equinox`], which
+/// looks at `RADESYS` to decide Besselian vs. Julian, ecliptic headers carry
+/// no `RADESYS` card, so the value is always taken to be a Julian year.
+fn parse_ecliptic_equinox(header: &WCSHeader) -> Epoch {
+    let value = match header.get_float("EQUINOX").or_else(|| header.get_float("EPOCH")) {
+        Some(Ok(value)) => value,
+        _ => 2000.0,
+    };
+
+    Epoch::new(value, EpochFormat::Julian)
+}
+
+type Mat3 = [[f64; 3]; 3];
+
+/// Equatorial (ICRS/FK5 J2000) -> Galactic rotation matrix (Hipparcos frame,
+/// see e.g. Liu, Zhu & Zhang 2011 A&A 526).
+const EQ_TO_GAL: Mat3 = [
+    [-0.0548755604, -0.8734370902, -0.4838350155],
+    [0.4941094279, -0.4448296300, 0.7469822445],
+    [-0.8676661490, -0.1980763734, 0.4559837762],
+];
+
+/// Galactic -> Supergalactic rotation matrix (de Vaucouleurs, de Vaucouleurs & Corwin 1976).
+const GAL_TO_SGAL: Mat3 = [
+    [-0.735742574804, 0.677261296414, 0.0],
+    [-0.074553778365, -0.080991471307, 0.993922590400],
+    [0.673145302109, 0.731271165817, 0.110081262225],
+];
+
+/// Mean obliquity of the ecliptic at J2000 (IAU 1980), in arcseconds.
+const OBLIQUITY_J2000_ARCSEC: f64 = 84381.448;
+
+/// Mean obliquity of the ecliptic ε(T) at an equinox `t` Julian centuries
+/// from J2000 (IAU 1980, Lieske et al. 1977).
+fn mean_obliquity(t: f64) -> f64 {
+    utils::arcsec_to_radians(
+        OBLIQUITY_J2000_ARCSEC - 46.8150 * t - 0.00059 * t * t + 0.001813 * t * t * t,
+    )
+}
+
+/// Elliptic e-terms of aberration affecting FK4 (but not FK4-NO-E) mean places,
+/// as a Cartesian vector in the B1950 frame (Standish 1982).
+const FK4_E_TERMS: [f64; 3] = [-1.62557e-6, -0.31919e-6, -0.13843e-6];
+
+fn lonlat_to_xyz(lonlat: LonLat) -> [f64; 3] {
+    let (lon, lat) = (lonlat.lon(), lonlat.lat());
+
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn xyz_to_lonlat(v: [f64; 3]) -> LonLat {
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    let (x, y, z) = (v[0] / norm, v[1] / norm, v[2] / norm);
+
+    let lon = y.atan2(x);
+    let lon = if lon < 0.0 {
+        lon + 2.0 * std::f64::consts::PI
+    } else {
+        lon
+    };
+
+    LonLat::new(lon, z.asin())
+}
+
+fn mat_vec_mul(m: &Mat3, v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut out = [[0.0; 3]; 3];
+
+    for i in 0..3 {
+        for j in 0..3 {
+            out[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+        }
+    }
+
+    out
+}
+
+fn transpose(m: &Mat3) -> Mat3 {
+    [
+        [m[0][0], m[1][0], m[2][0]],
+        [m[0][1], m[1][1], m[2][1]],
+        [m[0][2], m[1][2], m[2][2]],
+    ]
+}
+
+fn rot_x(angle: f64) -> Mat3 {
+    let (s, c) = angle.sin_cos();
+
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+fn rot_y(angle: f64) -> Mat3 {
+    let (s, c) = angle.sin_cos();
+
+    [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+fn rot_z(angle: f64) -> Mat3 {
+    let (s, c) = angle.sin_cos();
+
+    [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+/// Builds a precession matrix from three (zeta, z, theta) rotation angles, following
+/// the classical R3(-z) R2(theta) R3(-zeta) composition (Lieske 1979).
+fn precession_matrix(zeta: f64, z: f64, theta: f64) -> Mat3 {
+    mat_mul(&mat_mul(&rot_z(-z), &rot_y(theta)), &rot_z(-zeta))
+}
+
+/// IAU 1976 precession angles from J2000 to an equinox `t` Julian centuries away.
+fn precession_angles_iau1976(t: f64) -> (f64, f64, f64) {
+    let zeta = utils::arcsec_to_radians(2306.2181 * t + 0.30188 * t * t + 0.017998 * t * t * t);
+    let z = utils::arcsec_to_radians(2306.2181 * t + 1.09468 * t * t + 0.018203 * t * t * t);
+    let theta = utils::arcsec_to_radians(2004.3109 * t - 0.42665 * t * t - 0.041833 * t * t * t);
+
+    (zeta, z, theta)
+}
+
+/// Newcomb's precession angles from B1950 to an equinox `t` tropical centuries away,
+/// as used by the classical FK4 system.
+fn precession_angles_newcomb(t: f64) -> (f64, f64, f64) {
+    let zeta = utils::arcsec_to_radians(2304.948 * t + 0.302 * t * t + 0.0179 * t * t * t);
+    let z = utils::arcsec_to_radians(2304.948 * t + 1.093 * t * t + 0.0192 * t * t * t);
+    let theta = utils::arcsec_to_radians(2004.255 * t - 0.426 * t * t - 0.0416 * t * t * t);
+
+    (zeta, z, theta)
+}
+
+/// Removes the FK4 elliptic e-terms of aberration from a unit position vector,
+/// projecting the aberration vector out of the tangent plane at `v` (Standish 1982).
+fn remove_e_terms(v: [f64; 3]) -> [f64; 3] {
+    let a = FK4_E_TERMS;
+    let dot = v[0] * a[0] + v[1] * a[1] + v[2] * a[2];
+
+    [
+        v[0] - a[0] + dot * v[0],
+        v[1] - a[1] + dot * v[1],
+        v[2] - a[2] + dot * v[2],
+    ]
+}
+
+/// Adds the FK4 elliptic e-terms of aberration back onto a mean FK5 position,
+/// the inverse of [`remove_e_terms`].
+fn add_e_terms(v: [f64; 3]) -> [f64; 3] {
+    let a = FK4_E_TERMS;
+    let dot = v[0] * a[0] + v[1] * a[1] + v[2] * a[2];
+
+    [
+        v[0] + a[0] - dot * v[0],
+        v[1] + a[1] - dot * v[1],
+        v[2] + a[2] - dot * v[2],
+    ]
+}
+
+/// Rotates a FK4 B1950 mean place onto the FK5 J2000 frame: removes the e-terms
+/// (when `has_e_terms`) and precesses with Newcomb's angles. No separate FK4 -> FK5
+/// frame bias or equinox correction is applied.
+fn fk4_b1950_to_fk5_j2000(v: [f64; 3], has_e_terms: bool) -> [f64; 3] {
+    let v = if has_e_terms { remove_e_terms(v) } else { v };
+
+    let (zeta, z, theta) = precession_angles_newcomb(0.5);
+    mat_vec_mul(&precession_matrix(zeta, z, theta), v)
+}
+
+/// The inverse of [`fk4_b1950_to_fk5_j2000`].
+fn fk5_j2000_to_fk4_b1950(v: [f64; 3], has_e_terms: bool) -> [f64; 3] {
+    let (zeta, z, theta) = precession_angles_newcomb(0.5);
+    let v = mat_vec_mul(&transpose(&precession_matrix(zeta, z, theta)), v);
+
+    if has_e_terms {
+        add_e_terms(v)
+    } else {
+        v
+    }
+}
+
+/// Converts a unit vector expressed in the frame described by `radesys`/`equinox`
+/// (as carried by [`CooSystem::CUSTOM`]) into the common FK5/ICRS J2000 reference frame.
+///
+/// `GAPPT` apparent places are reduced to this mean frame by precessing from
+/// the epoch of observation; nutation, annual aberration and light deflection
+/// are not removed, so the result retains their sub-arcsecond contribution.
+fn custom_to_j2000(
+    v: [f64; 3],
+    radesys: &RadeSys,
+    equinox: &Epoch,
+    obs_epoch: Option<&Epoch>,
+) -> Result<[f64; 3], Error> {
+    match radesys {
+        RadeSys::ICRS => Ok(v),
+        RadeSys::Fk5 => {
+            let t = equinox.julian_centuries_from_j2000();
+            let (zeta, z, theta) = precession_angles_iau1976(t);
+
+            Ok(mat_vec_mul(&transpose(&precession_matrix(zeta, z, theta)), v))
+        }
+        RadeSys::Fk4 | RadeSys::Fk4NoE => {
+            let t = equinox.besselian_centuries_from_b1950();
+            let (zeta, z, theta) = precession_angles_newcomb(t);
+            let v_b1950 = mat_vec_mul(&transpose(&precession_matrix(zeta, z, theta)), v);
+
+            Ok(fk4_b1950_to_fk5_j2000(
+                v_b1950,
+                matches!(radesys, RadeSys::Fk4),
+            ))
+        }
+        RadeSys::GAPPT => {
+            let obs_epoch = obs_epoch.ok_or(Error::MandatoryWCSKeywordsMissing("DATE-OBS"))?;
+            let t = obs_epoch.julian_centuries_from_j2000();
+            let (zeta, z, theta) = precession_angles_iau1976(t);
+
+            Ok(mat_vec_mul(&transpose(&precession_matrix(zeta, z, theta)), v))
+        }
+    }
+}
+
+/// The inverse of [`custom_to_j2000`].
+fn j2000_to_custom(
+    v: [f64; 3],
+    radesys: &RadeSys,
+    equinox: &Epoch,
+    obs_epoch: Option<&Epoch>,
+) -> Result<[f64; 3], Error> {
+    match radesys {
+        RadeSys::ICRS => Ok(v),
+        RadeSys::Fk5 => {
+            let t = equinox.julian_centuries_from_j2000();
+            let (zeta, z, theta) = precession_angles_iau1976(t);
+
+            Ok(mat_vec_mul(&precession_matrix(zeta, z, theta), v))
+        }
+        RadeSys::Fk4 | RadeSys::Fk4NoE => {
+            let v_b1950 = fk5_j2000_to_fk4_b1950(v, matches!(radesys, RadeSys::Fk4));
+
+            let t = equinox.besselian_centuries_from_b1950();
+            let (zeta, z, theta) = precession_angles_newcomb(t);
+
+            Ok(mat_vec_mul(&precession_matrix(zeta, z, theta), v_b1950))
+        }
+        RadeSys::GAPPT => {
+            let obs_epoch = obs_epoch.ok_or(Error::MandatoryWCSKeywordsMissing("DATE-OBS"))?;
+            let t = obs_epoch.julian_centuries_from_j2000();
+            let (zeta, z, theta) = precession_angles_iau1976(t);
+
+            Ok(mat_vec_mul(&precession_matrix(zeta, z, theta), v))
+        }
+    }
+}
+
+/// Converts a unit vector expressed in `coo` into the common FK5/ICRS J2000 frame.
+fn to_j2000(v: [f64; 3], coo: &CooSystem) -> Result<[f64; 3], Error> {
+    match coo {
+        CooSystem::EQUATORIAL => Ok(v),
+        CooSystem::GALACTIC => Ok(mat_vec_mul(&transpose(&EQ_TO_GAL), v)),
+        CooSystem::ECLIPTIC { equinox } | CooSystem::HELIOECLIPTIC { equinox } => {
+            let eps = mean_obliquity(equinox.julian_centuries_from_j2000());
+            Ok(mat_vec_mul(&transpose(&rot_x(eps)), v))
+        }
+        CooSystem::SUPERGALACTIC => {
+            let gal = mat_vec_mul(&transpose(&GAL_TO_SGAL), v);
+            Ok(mat_vec_mul(&transpose(&EQ_TO_GAL), gal))
+        }
+        CooSystem::CUSTOM {
+            radesys,
+            equinox,
+            obs_epoch,
+        } => custom_to_j2000(v, radesys, equinox, obs_epoch.as_ref()),
+    }
+}
+
+/// The inverse of [`to_j2000`].
+fn from_j2000(v: [f64; 3], coo: &CooSystem) -> Result<[f64; 3], Error> {
+    match coo {
+        CooSystem::EQUATORIAL => Ok(v),
+        CooSystem::GALACTIC => Ok(mat_vec_mul(&EQ_TO_GAL, v)),
+        CooSystem::ECLIPTIC { equinox } | CooSystem::HELIOECLIPTIC { equinox } => {
+            let eps = mean_obliquity(equinox.julian_centuries_from_j2000());
+            Ok(mat_vec_mul(&rot_x(eps), v))
+        }
+        CooSystem::SUPERGALACTIC => {
+            let gal = mat_vec_mul(&EQ_TO_GAL, v);
+            Ok(mat_vec_mul(&GAL_TO_SGAL, gal))
+        }
+        CooSystem::CUSTOM {
+            radesys,
+            equinox,
+            obs_epoch,
+        } => j2000_to_custom(v, radesys, equinox, obs_epoch.as_ref()),
+    }
+}
+
+/// Transforms a sky position from one coordinate system to another, composing the
+/// standard Galactic, Ecliptic and FK4/FK5/ICRS rotations as 3x3 matrices acting on
+/// Cartesian unit vectors, via the common FK5/ICRS J2000 frame.
+pub fn transform(lonlat: LonLat, from: &CooSystem, to: &CooSystem) -> Result<LonLat, Error> {
+    let v = lonlat_to_xyz(lonlat);
+    let v_j2000 = to_j2000(v, from)?;
+    let v_to = from_j2000(v_j2000, to)?;
+
+    Ok(xyz_to_lonlat(v_to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epoch::EpochFormat;
+
+    fn assert_close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() < tol, "{} vs {} (tol {})", a, b, tol);
+    }
+
+    /// The IAU-defined North Galactic Pole, in ICRS/J2000 equatorial
+    /// coordinates, is RA 192.85948 deg, Dec +27.12825 deg (Blaauw et al.
+    /// 1960; the values baked into `EQ_TO_GAL`).
+    #[test]
+    fn galactic_pole_matches_icrs_ngp() {
+        let galactic_pole = LonLat::new(0.0, std::f64::consts::FRAC_PI_2);
+        let equatorial =
+            transform(galactic_pole, &CooSystem::GALACTIC, &CooSystem::EQUATORIAL).unwrap();
+
+        assert_close(equatorial.lon().to_degrees(), 192.85948, 1e-2);
+        assert_close(equatorial.lat().to_degrees(), 27.12825, 1e-2);
+    }
+
+    #[test]
+    fn equatorial_galactic_round_trip() {
+        let original = LonLat::new(1.1, 0.4);
+        let galactic = transform(original, &CooSystem::EQUATORIAL, &CooSystem::GALACTIC).unwrap();
+        let back = transform(galactic, &CooSystem::GALACTIC, &CooSystem::EQUATORIAL).unwrap();
+
+        assert_close(original.lon(), back.lon(), 1e-9);
+        assert_close(original.lat(), back.lat(), 1e-9);
+    }
+
+    #[test]
+    fn fk4_b1950_fk5_j2000_round_trip() {
+        // Arbitrarily chosen FK4 B1950 star position.
+        let original = LonLat::new(150.0_f64.to_radians(), 40.0_f64.to_radians());
+
+        let fk4 = CooSystem::CUSTOM {
+            radesys: RadeSys::Fk4,
+            equinox: Epoch::new(1950.0, EpochFormat::Besselian),
+            obs_epoch: None,
+        };
+        let fk5 = CooSystem::CUSTOM {
+            radesys: RadeSys::Fk5,
+            equinox: Epoch::new(2000.0, EpochFormat::Julian),
+            obs_epoch: None,
+        };
+
+        let converted = transform(original, &fk4, &fk5).unwrap();
+        // FK4 B1950 -> FK5 J2000 is a frame change plus ~0.5 centuries of
+        // precession, so the position should have moved appreciably.
+        assert!(crate::utils::angular_dist(original, converted) > 1e-4);
+
+        let back = transform(converted, &fk5, &fk4).unwrap();
+        assert_close(original.lon(), back.lon(), 1e-9);
+        assert_close(original.lat(), back.lat(), 1e-9);
+    }
+
+    /// Unlike the round trip above (which would also pass for a
+    /// systematically wrong transform, e.g. a flipped sign or swapped
+    /// matrix), this checks `fk4_b1950_to_fk5_j2000` against an external
+    /// reference pair: the IAU 1958 FK4 B1950.0 North Galactic Pole,
+    /// RA 192.25 deg, Dec +27.4 deg, precesses to RA 192.85948 deg,
+    /// Dec +27.12825 deg in FK5 J2000 (the same published J2000 NGP value
+    /// already used by `galactic_pole_matches_icrs_ngp`).
+    #[test]
+    fn fk4_b1950_ngp_matches_published_fk5_j2000_ngp() {
+        let fk4_ngp = LonLat::new(192.25_f64.to_radians(), 27.4_f64.to_radians());
+
+        let fk4 = CooSystem::CUSTOM {
+            radesys: RadeSys::Fk4,
+            equinox: Epoch::new(1950.0, EpochFormat::Besselian),
+            obs_epoch: None,
+        };
+        let fk5 = CooSystem::CUSTOM {
+            radesys: RadeSys::Fk5,
+            equinox: Epoch::new(2000.0, EpochFormat::Julian),
+            obs_epoch: None,
+        };
+
+        let fk5_ngp = transform(fk4_ngp, &fk4, &fk5).unwrap();
+
+        assert_close(fk5_ngp.lon().to_degrees(), 192.85948, 1e-2);
+        assert_close(fk5_ngp.lat().to_degrees(), 27.12825, 1e-2);
+    }
+
+    /// The north ecliptic pole sits at RA 270 degrees, at a declination of 90
+    /// degrees minus the mean obliquity; J2000 should match the well-known
+    /// IAU 1980 constant of 23.4392911 degrees (Dec 66.5607089 degrees).
+    #[test]
+    fn ecliptic_j2000_pole_matches_known_obliquity() {
+        let ecliptic_pole = LonLat::new(0.0, std::f64::consts::FRAC_PI_2);
+        let ecliptic = CooSystem::ECLIPTIC {
+            equinox: Epoch::new(2000.0, EpochFormat::Julian),
+        };
+
+        let equatorial = transform(ecliptic_pole, &ecliptic, &CooSystem::EQUATORIAL).unwrap();
+
+        assert_close(equatorial.lon().to_degrees(), 270.0, 1e-9);
+        assert_close(equatorial.lat().to_degrees(), 90.0 - 23.4392911, 1e-6);
+    }
+
+    /// A non-J2000 equinox should pick up the ε(T) secular drift instead of
+    /// silently reusing the fixed J2000 obliquity.
+    #[test]
+    fn ecliptic_b1950_pole_differs_from_j2000_obliquity() {
+        let ecliptic_pole = LonLat::new(0.0, std::f64::consts::FRAC_PI_2);
+        let ecliptic_b1950 = CooSystem::HELIOECLIPTIC {
+            equinox: Epoch::new(1950.0, EpochFormat::Besselian),
+        };
+
+        let equatorial = transform(ecliptic_pole, &ecliptic_b1950, &CooSystem::EQUATORIAL).unwrap();
+
+        assert!((equatorial.lat().to_degrees() - (90.0 - 23.4392911)).abs() > 1e-4);
+    }
+}
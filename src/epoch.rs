@@ -0,0 +1,215 @@
+//! Observation-epoch and equinox handling
+//!
+//! FITS headers express epochs either in the Besselian convention (tropical
+//! years, used by the old FK4 system) or the Julian convention (365.25-day
+//! years, used by FK5/ICRS). This module ties a bare `f64` year to the
+//! convention it was written in and converts between the two via the Julian
+//! day number, following the standard formulae:
+//! B = 1900.0 + (JD - 2415020.31352) / 365.242198781
+//! J = 2000.0 + (JD - 2451545.0) / 365.25
+
+use crate::coo_system::RadeSys;
+use crate::error::Error;
+use crate::header::WCSHeader;
+
+const B1900_JD: f64 = 2415020.31352;
+const B1950_TROPICAL_YEAR: f64 = 365.242198781;
+const J2000_JD: f64 = 2451545.0;
+const J2000_JULIAN_YEAR: f64 = 365.25;
+
+/// Tags whether an epoch/equinox value is expressed in the Besselian or
+/// Julian convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EpochFormat {
+    Besselian,
+    Julian,
+}
+
+/// An observation epoch or equinox, tagged with the time-scale format it was
+/// given in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Epoch {
+    value: f64,
+    format: EpochFormat,
+}
+
+impl Epoch {
+    pub fn new(value: f64, format: EpochFormat) -> Self {
+        Epoch { value, format }
+    }
+
+    pub fn from_jd(jd: f64) -> Self {
+        Epoch {
+            value: 2000.0 + (jd - J2000_JD) / J2000_JULIAN_YEAR,
+            format: EpochFormat::Julian,
+        }
+    }
+
+    pub fn format(&self) -> EpochFormat {
+        self.format
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// The Julian day number corresponding to this epoch.
+    pub fn to_jd(&self) -> f64 {
+        match self.format {
+            EpochFormat::Besselian => B1900_JD + (self.value - 1900.0) * B1950_TROPICAL_YEAR,
+            EpochFormat::Julian => J2000_JD + (self.value - 2000.0) * J2000_JULIAN_YEAR,
+        }
+    }
+
+    /// This epoch expressed as a Besselian year.
+    pub fn to_besselian(&self) -> f64 {
+        match self.format {
+            EpochFormat::Besselian => self.value,
+            EpochFormat::Julian => 1900.0 + (self.to_jd() - B1900_JD) / B1950_TROPICAL_YEAR,
+        }
+    }
+
+    /// This epoch expressed as a Julian year.
+    pub fn to_julian(&self) -> f64 {
+        match self.format {
+            EpochFormat::Julian => self.value,
+            EpochFormat::Besselian => 2000.0 + (self.to_jd() - J2000_JD) / J2000_JULIAN_YEAR,
+        }
+    }
+
+    /// Julian centuries elapsed from J2000 to this epoch, the time unit the
+    /// IAU 1976 precession angles are expressed in.
+    pub fn julian_centuries_from_j2000(&self) -> f64 {
+        (self.to_julian() - 2000.0) / 100.0
+    }
+
+    /// Tropical centuries elapsed from B1950 to this epoch, the time unit
+    /// Newcomb's precession angles are expressed in.
+    pub fn besselian_centuries_from_b1950(&self) -> f64 {
+        (self.to_besselian() - 1950.0) / 100.0
+    }
+
+    /// Parses the `EQUINOX` (or legacy `EPOCH`) card, defaulting to B1950 for
+    /// `Fk4`/`Fk4NoE` headers and J2000 for everything else, per the FITS WCS
+    /// convention that pre-1984 `RADESYS` values imply a Besselian equinox.
+    pub fn parse_equinox(header: &WCSHeader, radesys: &RadeSys) -> Result<Self, Error> {
+        let format = match radesys {
+            RadeSys::Fk4 | RadeSys::Fk4NoE => EpochFormat::Besselian,
+            RadeSys::Fk5 | RadeSys::ICRS | RadeSys::GAPPT => EpochFormat::Julian,
+        };
+
+        let value = match header.get_float("EQUINOX").or_else(|| header.get_float("EPOCH")) {
+            Some(value) => value?,
+            None => match format {
+                EpochFormat::Besselian => 1950.0,
+                EpochFormat::Julian => 2000.0,
+            },
+        };
+
+        Ok(Epoch::new(value, format))
+    }
+
+    /// Parses the observation epoch from `DATE-OBS` (preferred) or `MJD-OBS`,
+    /// returning `None` if neither card is present.
+    pub fn parse_obs_epoch(header: &WCSHeader) -> Option<Self> {
+        if let Some(date_obs) = header.get_date_obs() {
+            return parse_date_obs_jd(date_obs).map(Epoch::from_jd);
+        }
+
+        match header.get_float("MJD-OBS") {
+            Some(Ok(mjd)) => Some(Epoch::from_jd(mjd + 2400000.5)),
+            _ => None,
+        }
+    }
+}
+
+/// Converts an ISO-8601 `DATE-OBS` string (`YYYY-MM-DD` or
+/// `YYYY-MM-DD'T'HH:MM:SS`) to a Julian day number.
+fn parse_date_obs_jd(date_obs: &str) -> Option<f64> {
+    let mut parts = date_obs.splitn(2, 'T');
+    let date_part = parts.next()?;
+    let time_part = parts.next();
+
+    let mut date_it = date_part.split('-');
+    let year: i32 = date_it.next()?.parse().ok()?;
+    let month: i32 = date_it.next()?.parse().ok()?;
+    let day: f64 = date_it.next()?.parse().ok()?;
+
+    let day_frac = time_part.map_or(0.0, |time_part| {
+        let mut time_it = time_part.split(':');
+        let hour: f64 = time_it.next().and_then(|v| v.parse().ok()).unwrap_or(0.0);
+        let minute: f64 = time_it.next().and_then(|v| v.parse().ok()).unwrap_or(0.0);
+        let second: f64 = time_it.next().and_then(|v| v.parse().ok()).unwrap_or(0.0);
+
+        (hour + minute / 60.0 + second / 3600.0) / 24.0
+    });
+
+    Some(julian_day_number(year, month, day + day_frac))
+}
+
+/// Gregorian calendar date to Julian day number (Meeus, *Astronomical
+/// Algorithms*, ch. 7).
+fn julian_day_number(year: i32, month: i32, day: f64) -> f64 {
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+
+    let a = (y as f64 / 100.0).floor();
+    let b = 2.0 - a + (a / 4.0).floor();
+
+    (365.25 * (y as f64 + 4716.0)).floor() + (30.6001 * (m as f64 + 1.0)).floor() + day + b - 1524.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() < tol, "{} vs {} (tol {})", a, b, tol);
+    }
+
+    #[test]
+    fn besselian_b1950_matches_defining_jd() {
+        let epoch = Epoch::new(1950.0, EpochFormat::Besselian);
+        // The standard epoch B1950.0, a commonly quoted reference value.
+        assert_close(epoch.to_jd(), 2433282.4235, 1e-3);
+    }
+
+    #[test]
+    fn julian_j2000_matches_defining_jd() {
+        let epoch = Epoch::new(2000.0, EpochFormat::Julian);
+        assert_close(epoch.to_jd(), J2000_JD, 1e-9);
+    }
+
+    #[test]
+    fn from_jd_round_trips_through_besselian_and_julian() {
+        let epoch = Epoch::new(1950.0, EpochFormat::Besselian);
+
+        assert_close(epoch.to_besselian(), 1950.0, 1e-9);
+        // B1950.0 in the Julian convention, a standard cross-check value.
+        assert_close(epoch.to_julian(), 1949.9997904423, 1e-6);
+
+        let from_jd = Epoch::from_jd(epoch.to_jd());
+        assert_close(from_jd.to_besselian(), 1950.0, 1e-6);
+    }
+
+    #[test]
+    fn centuries_from_reference_epoch_are_zero_at_the_reference() {
+        let j2000 = Epoch::new(2000.0, EpochFormat::Julian);
+        assert_close(j2000.julian_centuries_from_j2000(), 0.0, 1e-12);
+
+        let b1950 = Epoch::new(1950.0, EpochFormat::Besselian);
+        assert_close(b1950.besselian_centuries_from_b1950(), 0.0, 1e-12);
+    }
+
+    #[test]
+    fn date_obs_parses_to_expected_jd() {
+        // 2000-01-01T12:00:00 is exactly J2000.0 (JD 2451545.0).
+        let jd = parse_date_obs_jd("2000-01-01T12:00:00").unwrap();
+        assert_close(jd, J2000_JD, 1e-6);
+    }
+}
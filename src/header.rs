@@ -1,24 +1,257 @@
 use crate::error::Error;
 use std::collections::HashMap;
 
+const FITS_LINE_LENGTH: usize = 80;
+
+/// Per-axis keywords and the linear transform of a single WCS description,
+/// i.e. either the primary representation or one of the alternate ('A'-'Z'
+/// suffixed) representations a header may carry (FITS WCS Paper I, sec. 3.3).
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WCSAxes {
+    naxisn: Vec<u64>,
+    ctypen: Vec<String>,
+    crpixn: Vec<f64>,
+    crvaln: Vec<f64>,
+    cdeltn: Vec<f64>,
+    // keyed by "i_j" rather than (usize, usize) so this round-trips through
+    // serde formats (e.g. JSON/TOML) that require string map keys
+    pc: HashMap<String, f64>,
+    cd: HashMap<String, f64>,
+}
+
+fn matrix_key(i: usize, j: usize) -> String {
+    format!("{}_{}", i, j)
+}
+
+impl WCSAxes {
+    fn ensure_len(v: &mut Vec<f64>, len: usize, default: f64) {
+        if v.len() < len {
+            v.resize(len, default);
+        }
+    }
+
+    /// All per-axis keywords are 1-based (`CRVAL1`, not `CRVAL0`); this
+    /// guards against a stray `...0`-suffixed card indexing into axis 0.
+    fn set_naxisn(&mut self, idx: usize, value: u64) {
+        let Some(idx) = idx.checked_sub(1) else {
+            return;
+        };
+        if self.naxisn.len() <= idx {
+            self.naxisn.resize(idx + 1, 0);
+        }
+        self.naxisn[idx] = value;
+    }
+
+    fn set_ctypen(&mut self, idx: usize, value: String) {
+        let Some(idx) = idx.checked_sub(1) else {
+            return;
+        };
+        if self.ctypen.len() <= idx {
+            self.ctypen.resize(idx + 1, String::new());
+        }
+        self.ctypen[idx] = value;
+    }
+
+    fn set_crpixn(&mut self, idx: usize, value: f64) {
+        let Some(idx) = idx.checked_sub(1) else {
+            return;
+        };
+        Self::ensure_len(&mut self.crpixn, idx + 1, 0.0);
+        self.crpixn[idx] = value;
+    }
+
+    fn set_crvaln(&mut self, idx: usize, value: f64) {
+        let Some(idx) = idx.checked_sub(1) else {
+            return;
+        };
+        Self::ensure_len(&mut self.crvaln, idx + 1, 0.0);
+        self.crvaln[idx] = value;
+    }
+
+    fn set_cdeltn(&mut self, idx: usize, value: f64) {
+        let Some(idx) = idx.checked_sub(1) else {
+            return;
+        };
+        Self::ensure_len(&mut self.cdeltn, idx + 1, 1.0);
+        self.cdeltn[idx] = value;
+    }
+
+    /// The number of axes this WCS description covers (from `NAXISn`/`CRPIXn`/
+    /// `CRVALn`/`CDELTn`/`CTYPEn`).
+    pub fn naxis(&self) -> usize {
+        self.ctypen
+            .len()
+            .max(self.crpixn.len())
+            .max(self.crvaln.len())
+            .max(self.cdeltn.len())
+            .max(self.naxisn.len())
+    }
+
+    /// `idx` is 1-based, matching the FITS `NAXISn` keyword; `idx == 0` (no
+    /// corresponding card) returns `None` rather than panicking.
+    pub fn naxisn(&self, idx: usize) -> Option<u64> {
+        let idx = idx.checked_sub(1)?;
+        self.naxisn.get(idx).copied().filter(|&n| n > 0)
+    }
+
+    /// `idx` is 1-based, matching the FITS `CTYPEn` keyword; `idx == 0`
+    /// returns `None` rather than panicking.
+    pub fn ctypen(&self, idx: usize) -> Option<&str> {
+        let idx = idx.checked_sub(1)?;
+        self.ctypen
+            .get(idx)
+            .map(String::as_str)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// `idx` is 1-based, matching the FITS `CRPIXn` keyword; `idx == 0`
+    /// returns the FITS default (0.0) rather than panicking.
+    pub fn crpixn(&self, idx: usize) -> f64 {
+        idx.checked_sub(1)
+            .and_then(|idx| self.crpixn.get(idx).copied())
+            .unwrap_or(0.0)
+    }
+
+    /// `idx` is 1-based, matching the FITS `CRVALn` keyword; `idx == 0`
+    /// returns the FITS default (0.0) rather than panicking.
+    pub fn crvaln(&self, idx: usize) -> f64 {
+        idx.checked_sub(1)
+            .and_then(|idx| self.crvaln.get(idx).copied())
+            .unwrap_or(0.0)
+    }
+
+    /// `idx` is 1-based, matching the FITS `CDELTn` keyword; `idx == 0`
+    /// returns the FITS default (1.0) rather than panicking.
+    pub fn cdeltn(&self, idx: usize) -> f64 {
+        idx.checked_sub(1)
+            .and_then(|idx| self.cdeltn.get(idx).copied())
+            .unwrap_or(1.0)
+    }
+
+    /// The N-dimensional linear transform matrix, reconstructed from the
+    /// `CDi_j` matrix if present, otherwise from `PCi_j` (defaulting to the
+    /// identity) scaled by `CDELTi`, per the FITS default rules.
+    pub fn linear_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.naxis().max(1);
+
+        if !self.cd.is_empty() {
+            (1..=n)
+                .map(|i| {
+                    (1..=n)
+                        .map(|j| *self.cd.get(&matrix_key(i, j)).unwrap_or(&0.0))
+                        .collect()
+                })
+                .collect()
+        } else {
+            (1..=n)
+                .map(|i| {
+                    (1..=n)
+                        .map(|j| {
+                            let pc = self
+                                .pc
+                                .get(&matrix_key(i, j))
+                                .copied()
+                                .unwrap_or(if i == j { 1.0 } else { 0.0 });
+
+                            pc * self.cdeltn(i)
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WCSHeader {
-    naxis1: u64,
-    naxis2: u64,
-    ctype1: String,
-    ctype2: String,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_wcs_axes"))]
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_wcs_axes"))]
+    wcs_axes: HashMap<char, WCSAxes>,
+    radesys: Option<String>,
+    date_obs: Option<String>,
     cards: HashMap<String, f64>,
 }
 
-const FITS_LINE_LENGTH: usize = 80;
+#[cfg(feature = "serde")]
+fn serialize_wcs_axes<S>(
+    wcs_axes: &HashMap<char, WCSAxes>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    // '\0' is not a valid JSON/TOML object key, so the primary representation
+    // is stored under the empty string instead.
+    let as_strings: HashMap<String, &WCSAxes> = wcs_axes
+        .iter()
+        .map(|(&alt, axes)| (if alt == '\0' { String::new() } else { alt.to_string() }, axes))
+        .collect();
+
+    serde::Serialize::serialize(&as_strings, serializer)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_wcs_axes<'de, D>(deserializer: D) -> Result<HashMap<char, WCSAxes>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let as_strings: HashMap<String, WCSAxes> = serde::Deserialize::deserialize(deserializer)?;
+
+    Ok(as_strings
+        .into_iter()
+        .map(|(suffix, axes)| (suffix.chars().next().unwrap_or('\0'), axes))
+        .collect())
+}
+
+/// Splits an axis-indexed keyword such as `CRVAL2` or its alternate-WCS form
+/// `CRVAL2A` into the axis index and the alternate-WCS suffix ('\0' for the
+/// primary representation).
+fn split_indexed_key(key: &str, prefix: &str) -> Option<(usize, char)> {
+    let rest = key.strip_prefix(prefix)?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let last = rest.as_bytes()[rest.len() - 1];
+    let (digits, alt) = if last.is_ascii_uppercase() {
+        (&rest[..rest.len() - 1], last as char)
+    } else {
+        (rest, '\0')
+    };
+
+    Some((digits.parse().ok()?, alt))
+}
+
+/// Splits a matrix keyword such as `PC1_2` or `CD1_2A` into its `(i, j)`
+/// indices and the alternate-WCS suffix.
+fn split_matrix_key(key: &str, prefix: &str) -> Option<(usize, usize, char)> {
+    let rest = key.strip_prefix(prefix)?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let last = rest.as_bytes()[rest.len() - 1];
+    let (core, alt) = if last.is_ascii_uppercase() {
+        (&rest[..rest.len() - 1], last as char)
+    } else {
+        (rest, '\0')
+    };
+
+    let mut it = core.split('_');
+    let i: usize = it.next()?.parse().ok()?;
+    let j: usize = it.next()?.parse().ok()?;
+
+    Some((i, j, alt))
+}
 
 impl WCSHeader {
     pub fn new(s: &str) -> Self {
+        let mut wcs_axes: HashMap<char, WCSAxes> = HashMap::new();
         let mut cards = HashMap::new();
-        let mut naxis1 = 0;
-        let mut naxis2 = 0;
-        let mut ctype1 = String::new();
-        let mut ctype2 = String::new();
+        let mut radesys = None;
+        let mut date_obs = None;
 
         let mut offset: usize = 0;
 
@@ -42,62 +275,231 @@ impl WCSHeader {
             // remove an optional comment (starting with '/') from the value
             let value = value.split('/').next().unwrap().trim();
 
-            match key {
-                "NAXIS1" => naxis1 = value.parse().unwrap(),
-                "NAXIS2" => naxis2 = value.parse().unwrap(),
-                "CTYPE1" => ctype1 = value.to_string().replace("'", ""),
-                "CTYPE2" => ctype2 = value.to_string().replace("'", ""),
-                _ => {
-                    if let Ok(value) = value.parse() {
-                        cards.insert(key.to_string(), value);
-                    }
+            if let Some((idx, alt)) = split_indexed_key(key, "NAXIS") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes.entry(alt).or_default().set_naxisn(idx, value);
+                }
+            } else if let Some((idx, alt)) = split_indexed_key(key, "CTYPE") {
+                wcs_axes
+                    .entry(alt)
+                    .or_default()
+                    .set_ctypen(idx, value.to_string().replace('\'', "").trim().to_string());
+            } else if let Some((idx, alt)) = split_indexed_key(key, "CRPIX") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes.entry(alt).or_default().set_crpixn(idx, value);
+                }
+            } else if let Some((idx, alt)) = split_indexed_key(key, "CRVAL") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes.entry(alt).or_default().set_crvaln(idx, value);
                 }
+            } else if let Some((idx, alt)) = split_indexed_key(key, "CDELT") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes.entry(alt).or_default().set_cdeltn(idx, value);
+                }
+            } else if let Some((i, j, alt)) = split_matrix_key(key, "PC") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes
+                        .entry(alt)
+                        .or_default()
+                        .pc
+                        .insert(matrix_key(i, j), value);
+                }
+            } else if let Some((i, j, alt)) = split_matrix_key(key, "CD") {
+                if let Ok(value) = value.parse() {
+                    wcs_axes
+                        .entry(alt)
+                        .or_default()
+                        .cd
+                        .insert(matrix_key(i, j), value);
+                }
+            } else if key == "RADESYS" {
+                radesys = Some(value.to_string().replace('\'', "").trim().to_string());
+            } else if key == "DATE-OBS" {
+                // DATE-OBS is a string card (ISO-8601), so it does not survive
+                // the generic numeric-only fallback below and must be kept as-is.
+                date_obs = Some(value.to_string().replace('\'', "").trim().to_string());
+            } else if let Ok(value) = value.parse() {
+                cards.insert(key.to_string(), value);
             }
         }
 
         WCSHeader {
-            naxis1,
-            naxis2,
-            ctype1,
-            ctype2,
+            wcs_axes,
+            radesys,
+            date_obs,
             cards,
         }
     }
 
-    pub fn get_naxisn(&self, idx: usize) -> Option<u64> {
-        let value = match idx {
-            1 => self.naxis1,
-            2 => self.naxis2,
-            _ => 0,
-        };
+    /// Returns the WCS axis description for the given alternate-WCS suffix
+    /// ('\0' for the primary representation, 'A'-'Z' for an alternate one).
+    pub fn get_wcs(&self, axis_set: char) -> Option<&WCSAxes> {
+        self.wcs_axes.get(&axis_set)
+    }
 
-        // check if value == 0
-        if value > 0 {
-            Some(value)
-        } else {
-            None
-        }
+    fn primary(&self) -> Option<&WCSAxes> {
+        self.get_wcs('\0')
     }
 
-    pub fn get_ctype(&self, idx: usize) -> Result<String, Error> {
-        let value = match idx {
-            1 => &self.ctype1,
-            2 => &self.ctype2,
-            _ => "",
-        };
+    pub fn get_date_obs(&self) -> Option<&str> {
+        self.date_obs.as_deref()
+    }
 
-        if value.is_empty() {
-            Err(Error::MandatoryWCSKeywordsMissing("CTYPE"))
-        } else {
-            Ok(value.to_string())
-        }
+    pub fn get_naxisn(&self, idx: usize) -> Option<u64> {
+        self.primary().and_then(|axes| axes.naxisn(idx))
+    }
+
+    pub fn get_ctype(&self, idx: usize) -> Result<String, Error> {
+        self.primary()
+            .and_then(|axes| axes.ctypen(idx))
+            .map(str::to_string)
+            .ok_or(Error::MandatoryWCSKeywordsMissing("CTYPE"))
     }
 
     pub fn get_float(&self, key: &str) -> Option<Result<f64, Error>> {
-        if let Some(value) = self.cards.get(key.trim()) {
-            Some(Ok(*value))
-        } else {
-            None
+        self.cards.get(key.trim()).map(|value| Ok(*value))
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<Result<i64, Error>> {
+        self.cards.get(key.trim()).map(|value| Ok(*value as i64))
+    }
+
+    pub fn get_radesys(&self) -> Result<String, Error> {
+        self.radesys
+            .clone()
+            .ok_or(Error::MandatoryWCSKeywordsMissing("RADESYS"))
+    }
+
+    /// Serialises this (possibly modified) WCS back into fixed 80-column FITS
+    /// cards, terminated by an `END` card. `WCSHeader::new(h.to_fits_string())`
+    /// reproduces the same parsed structure for the keyword set this module
+    /// understands (NAXISj, CTYPEj, CRPIXj, CRVALj, CDELTj/CDi_j/PCi_j,
+    /// RADESYS, EQUINOX and the other numeric cards).
+    pub fn to_fits_string(&self) -> String {
+        let mut out = String::new();
+
+        let mut alts: Vec<char> = self.wcs_axes.keys().copied().collect();
+        alts.sort_by_key(|&alt| if alt == '\0' { 0u32 } else { alt as u32 });
+
+        for alt in alts {
+            let axes = &self.wcs_axes[&alt];
+            let suffix = if alt == '\0' { String::new() } else { alt.to_string() };
+
+            for idx in 1..=axes.naxis() {
+                if let Some(naxis) = axes.naxisn(idx) {
+                    out.push_str(&format_int_card(&format!("NAXIS{}{}", idx, suffix), naxis as i64));
+                }
+                if let Some(ctype) = axes.ctypen(idx) {
+                    out.push_str(&format_str_card(&format!("CTYPE{}{}", idx, suffix), ctype));
+                }
+                out.push_str(&format_float_card(&format!("CRPIX{}{}", idx, suffix), axes.crpixn(idx)));
+                out.push_str(&format_float_card(&format!("CRVAL{}{}", idx, suffix), axes.crvaln(idx)));
+
+                if axes.cd.is_empty() {
+                    out.push_str(&format_float_card(&format!("CDELT{}{}", idx, suffix), axes.cdeltn(idx)));
+                }
+            }
+
+            let matrix = if axes.cd.is_empty() { &axes.pc } else { &axes.cd };
+            let prefix = if axes.cd.is_empty() { "PC" } else { "CD" };
+
+            for (key, &value) in matrix {
+                out.push_str(&format_float_card(&format!("{}{}{}", prefix, key, suffix), value));
+            }
+        }
+
+        if let Some(radesys) = &self.radesys {
+            out.push_str(&format_str_card("RADESYS", radesys));
+        }
+
+        for (key, &value) in &self.cards {
+            out.push_str(&format_float_card(key, value));
+        }
+
+        if let Some(date_obs) = &self.date_obs {
+            out.push_str(&format_str_card("DATE-OBS", date_obs));
         }
+
+        out.push_str(&pad_card("END".to_string()));
+
+        out
+    }
+}
+
+fn pad_card(mut card: String) -> String {
+    if card.len() >= FITS_LINE_LENGTH {
+        card.truncate(FITS_LINE_LENGTH);
+    } else {
+        card.push_str(&" ".repeat(FITS_LINE_LENGTH - card.len()));
+    }
+
+    card
+}
+
+fn format_float_card(keyword: &str, value: f64) -> String {
+    // both forms print the shortest digits that parse back to the same f64,
+    // so the round-trip is faithful; scientific notation is used below 1e-4
+    // and from 1e15 upwards, where the fixed-point form gets needlessly wide
+    let formatted = if value != 0.0 && (value.abs() < 1e-4 || value.abs() >= 1e15) {
+        format!("{:e}", value)
+    } else {
+        format!("{:?}", value)
+    };
+
+    pad_card(format!("{:<8}= {:>20}", keyword, formatted))
+}
+
+fn format_int_card(keyword: &str, value: i64) -> String {
+    pad_card(format!("{:<8}= {:>20}", keyword, value))
+}
+
+fn format_str_card(keyword: &str, value: &str) -> String {
+    pad_card(format!("{:<8}= '{:<8}'", keyword, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_header() -> String {
+        let mut s = String::new();
+        s.push_str(&format_int_card("NAXIS1", 100));
+        s.push_str(&format_str_card("CTYPE1", "RA---TAN"));
+        s.push_str(&format_float_card("CRPIX1", 50.5));
+        s.push_str(&format_float_card("CRVAL1", 180.25));
+        s.push_str(&format_float_card("CDELT1", -0.0002777778));
+        s.push_str(&format_str_card("RADESYS", "ICRS"));
+        s.push_str(&format_str_card("DATE-OBS", "2020-01-01"));
+        s.push_str(&format_float_card("EQUINOX", 2000.0));
+        s.push_str(&pad_card("END".to_string()));
+        s
+    }
+
+    #[test]
+    fn to_fits_string_round_trips_through_new() {
+        let original = WCSHeader::new(&build_header());
+        let regenerated = WCSHeader::new(&original.to_fits_string());
+
+        assert_eq!(original.get_naxisn(1), regenerated.get_naxisn(1));
+        assert_eq!(
+            original.get_ctype(1).unwrap(),
+            regenerated.get_ctype(1).unwrap()
+        );
+
+        let original_axes = original.get_wcs('\0').unwrap();
+        let regenerated_axes = regenerated.get_wcs('\0').unwrap();
+        assert_eq!(original_axes.crpixn(1), regenerated_axes.crpixn(1));
+        assert_eq!(original_axes.crvaln(1), regenerated_axes.crvaln(1));
+        assert_eq!(original_axes.cdeltn(1), regenerated_axes.cdeltn(1));
+
+        assert_eq!(
+            original.get_radesys().unwrap(),
+            regenerated.get_radesys().unwrap()
+        );
+        assert_eq!(original.get_date_obs(), regenerated.get_date_obs());
+        assert_eq!(
+            original.get_float("EQUINOX").unwrap().unwrap(),
+            regenerated.get_float("EQUINOX").unwrap().unwrap()
+        );
     }
 }
@@ -31,8 +31,12 @@ pub trait WCSCanonicalProjection: CanonicalProjection {
     where
         Self: Sized,
     {
-        let crval1 = header.get_float("CRVAL1  ").unwrap_or(Ok(0.0))?;
-        let crval2 = header.get_float("CRVAL2  ").unwrap_or(Ok(0.0))?;
+        // CRVAL1/CRVAL2 are per-axis WCS keywords kept on the primary WCS
+        // axis description, not in the generic `cards` map `get_float` reads
+        // from; `crvaln` already defaults to 0.0 when the card is absent.
+        let axes = header.get_wcs('\0');
+        let crval1 = axes.map_or(0.0, |axes| axes.crvaln(1));
+        let crval2 = axes.map_or(0.0, |axes| axes.crvaln(2));
 
         let proj = Self::parse_internal_proj_params(header)?;
 
@@ -307,3 +311,50 @@ impl WCSCanonicalProjection for Coo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad_card(mut card: String) -> String {
+        card.push_str(&" ".repeat(80 - card.len()));
+        card
+    }
+
+    fn float_card(keyword: &str, value: f64) -> String {
+        pad_card(format!("{:<8}= {:.12}", keyword, value))
+    }
+
+    fn build_header(crval1: f64, crval2: f64) -> WCSHeader {
+        let mut s = String::new();
+        s.push_str(&float_card("CRVAL1", crval1));
+        s.push_str(&float_card("CRVAL2", crval2));
+        s.push_str(&pad_card("END".to_string()));
+
+        WCSHeader::new(&s)
+    }
+
+    /// Regression test for the projection center being silently read as
+    /// (0, 0) regardless of the header's CRVAL1/CRVAL2: `parse_proj` must
+    /// pick these up from the primary WCS axes, not the flat `cards` map.
+    #[test]
+    fn parse_proj_reads_crval_from_the_primary_wcs_axes() {
+        let header = build_header(180.0, 30.0);
+
+        let axes = header.get_wcs('\0').unwrap();
+        assert_eq!(axes.crvaln(1), 180.0);
+        assert_eq!(axes.crvaln(2), 30.0);
+
+        // Exercise parse_proj end-to-end on the same header: it must not
+        // error. The projection center it sets is not inspected here.
+        assert!(Tan::parse_proj(&header).is_ok());
+    }
+
+    #[test]
+    fn parse_proj_defaults_to_zero_without_crval_cards() {
+        let header = WCSHeader::new(&pad_card("END".to_string()));
+
+        assert!(header.get_wcs('\0').is_none());
+        assert!(Tan::parse_proj(&header).is_ok());
+    }
+}
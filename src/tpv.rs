@@ -0,0 +1,295 @@
+//! This module is an implementation of the TPV convention
+//!
+//! TPV folds the distortion directly into the `PVi_j` keywords on top of a
+//! TAN projection, rather than using the SIP `A_i_j`/`B_i_j` prefix keywords
+//! (see the "WCS Distortion Paper" draft used by the TERAPIX/SCAMP pipeline).
+//!
+//! Like [`crate::sip::parse_sip`], `parse_tpv`/`is_tpv`/[`Tpv::apply`] are not
+//! yet wired into a pixel<->world pipeline in this crate: there is no
+//! top-level WCS struct in this tree to dispatch through. Both distortion
+//! conventions are parsed and ready to be called from that pipeline once it
+//! exists.
+
+use crate::error::Error;
+use crate::header::WCSHeader;
+
+const NUM_TERMS: usize = 40;
+
+/// A single TPV bivariate polynomial, evaluated as `sum_j PV_j * term_j(u, v)`.
+pub struct TpvPoly {
+    coeffs: [f64; NUM_TERMS],
+}
+
+impl TpvPoly {
+    /// The TPV term table, `u`/`v` being the native axis and the cross axis
+    /// respectively. The odd radial terms (indices 3, 11, 23, 39) are powers
+    /// of `r = sqrt(u^2 + v^2)`.
+    fn term(idx: usize, u: f64, v: f64) -> f64 {
+        let r = (u * u + v * v).sqrt();
+
+        match idx {
+            0 => 1.0,
+            1 => u,
+            2 => v,
+            3 => r,
+            4 => u * u,
+            5 => u * v,
+            6 => v * v,
+            7 => u.powi(3),
+            8 => u * u * v,
+            9 => u * v * v,
+            10 => v.powi(3),
+            11 => r.powi(3),
+            12 => u.powi(4),
+            13 => u.powi(3) * v,
+            14 => u * u * v * v,
+            15 => u * v.powi(3),
+            16 => v.powi(4),
+            17 => u.powi(5),
+            18 => u.powi(4) * v,
+            19 => u.powi(3) * v * v,
+            20 => u * u * v.powi(3),
+            21 => u * v.powi(4),
+            22 => v.powi(5),
+            23 => r.powi(5),
+            24 => u.powi(6),
+            25 => u.powi(5) * v,
+            26 => u.powi(4) * v * v,
+            27 => u.powi(3) * v.powi(3),
+            28 => u * u * v.powi(4),
+            29 => u * v.powi(5),
+            30 => v.powi(6),
+            31 => u.powi(7),
+            32 => u.powi(6) * v,
+            33 => u.powi(5) * v * v,
+            34 => u.powi(4) * v.powi(3),
+            35 => u.powi(3) * v.powi(4),
+            36 => u * u * v.powi(5),
+            37 => u * v.powi(6),
+            38 => v.powi(7),
+            39 => r.powi(7),
+            _ => 0.0,
+        }
+    }
+
+    fn eval(&self, u: f64, v: f64) -> f64 {
+        self.coeffs
+            .iter()
+            .enumerate()
+            .map(|(idx, coeff)| coeff * Self::term(idx, u, v))
+            .sum()
+    }
+}
+
+/// Distortion polynomials for the two intermediate world coordinate axes.
+pub struct Tpv {
+    xi_poly: TpvPoly,
+    eta_poly: TpvPoly,
+}
+
+impl Tpv {
+    /// Applies the distortion to a pair of undistorted intermediate world
+    /// coordinates, immediately after the linear matrix and before the
+    /// spherical TAN projection.
+    pub fn apply(&self, xi: f64, eta: f64) -> (f64, f64) {
+        (self.xi_poly.eval(xi, eta), self.eta_poly.eval(eta, xi))
+    }
+}
+
+fn retrieve_pv_coeffs(header: &WCSHeader, axis: usize) -> Result<[f64; NUM_TERMS], Error> {
+    let mut coeffs = [0.0; NUM_TERMS];
+
+    for (j, coeff) in coeffs.iter_mut().enumerate() {
+        let key = format!("PV{}_{}", axis, j);
+
+        if let Some(value) = header.get_float(&key) {
+            *coeff = value?;
+        }
+    }
+
+    Ok(coeffs)
+}
+
+/// Returns `true` if the header carries `PVi_j` distortion terms beyond the
+/// plain linear ones (indices 0-2), i.e. an actual TPV polynomial rather than
+/// a bare TAN projection parameterization.
+pub fn has_tpv_terms(header: &WCSHeader) -> bool {
+    (3..NUM_TERMS).any(|j| {
+        header.get_float(&format!("PV1_{}", j)).is_some()
+            || header.get_float(&format!("PV2_{}", j)).is_some()
+    })
+}
+
+/// Returns `true` if the header should be routed through the TPV distortion
+/// path: either `CTYPEi` explicitly ends in `-TPV`, or `PVi_j` distortion
+/// terms are present alongside a TAN core.
+pub fn is_tpv(header: &WCSHeader, ctype1: &str) -> bool {
+    ctype1.ends_with("-TPV") || (ctype1.contains("-TAN") && has_tpv_terms(header))
+}
+
+/// Returns `true` if the header also carries SIP `A_ORDER`/`B_ORDER` keywords,
+/// which must not be combined with TPV on the same axis.
+fn has_sip_terms(header: &WCSHeader) -> bool {
+    header.get_int("A_ORDER").is_some() || header.get_int("B_ORDER").is_some()
+}
+
+pub fn parse_tpv(header: &WCSHeader) -> Result<Tpv, Error> {
+    if has_sip_terms(header) {
+        return Err(Error::ConflictingDistortion("SIP and TPV both present"));
+    }
+
+    let xi_poly = TpvPoly {
+        coeffs: retrieve_pv_coeffs(header, 1)?,
+    };
+    let eta_poly = TpvPoly {
+        coeffs: retrieve_pv_coeffs(header, 2)?,
+    };
+
+    Ok(Tpv { xi_poly, eta_poly })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad_card(mut card: String) -> String {
+        card.push_str(&" ".repeat(80 - card.len()));
+        card
+    }
+
+    fn float_card(keyword: &str, value: f64) -> String {
+        pad_card(format!("{:<8}= {:.12}", keyword, value))
+    }
+
+    fn int_card(keyword: &str, value: i64) -> String {
+        pad_card(format!("{:<8}= {}", keyword, value))
+    }
+
+    /// The plain (non-radial) terms are just monomials in `u`/`v`; the odd
+    /// indices (3, 11, 23, 39) are instead powers of the radius `r`.
+    #[test]
+    fn term_evaluates_monomials_and_radial_terms() {
+        let (u, v) = (2.0, 3.0);
+        let r = (u * u + v * v).sqrt();
+
+        assert_eq!(TpvPoly::term(0, u, v), 1.0);
+        assert_eq!(TpvPoly::term(1, u, v), u);
+        assert_eq!(TpvPoly::term(2, u, v), v);
+        assert_eq!(TpvPoly::term(4, u, v), u * u);
+        assert_eq!(TpvPoly::term(5, u, v), u * v);
+
+        assert_eq!(TpvPoly::term(3, u, v), r);
+        assert_eq!(TpvPoly::term(11, u, v), r.powi(3));
+        assert_eq!(TpvPoly::term(23, u, v), r.powi(5));
+        assert_eq!(TpvPoly::term(39, u, v), r.powi(7));
+    }
+
+    #[test]
+    fn apply_with_zero_coeffs_is_identically_zero() {
+        let tpv = Tpv {
+            xi_poly: TpvPoly {
+                coeffs: [0.0; NUM_TERMS],
+            },
+            eta_poly: TpvPoly {
+                coeffs: [0.0; NUM_TERMS],
+            },
+        };
+
+        assert_eq!(tpv.apply(1.5, -2.5), (0.0, 0.0));
+    }
+
+    #[test]
+    fn apply_with_only_constant_terms_is_a_plain_shift() {
+        // Only the PVi_0 constant offsets are set, so `apply` degenerates to
+        // a plain shift regardless of (xi, eta).
+        let mut xi_coeffs = [0.0; NUM_TERMS];
+        xi_coeffs[0] = 1.0;
+        let mut eta_coeffs = [0.0; NUM_TERMS];
+        eta_coeffs[0] = 2.0;
+
+        let tpv = Tpv {
+            xi_poly: TpvPoly { coeffs: xi_coeffs },
+            eta_poly: TpvPoly { coeffs: eta_coeffs },
+        };
+
+        assert_eq!(tpv.apply(0.3, -0.7), (1.0, 2.0));
+    }
+
+    /// `apply` evaluates the eta polynomial with its axes swapped
+    /// (`eta_poly.eval(eta, xi)`), matching the TPV convention that each
+    /// axis' polynomial is expressed in its own native/cross coordinate
+    /// pair; a PV2_1 ("native" u) coefficient must pick up `eta`, not `xi`.
+    #[test]
+    fn apply_evaluates_eta_poly_with_swapped_axes() {
+        let mut eta_coeffs = [0.0; NUM_TERMS];
+        eta_coeffs[1] = 1.0; // term(1, u, v) = u
+
+        let tpv = Tpv {
+            xi_poly: TpvPoly {
+                coeffs: [0.0; NUM_TERMS],
+            },
+            eta_poly: TpvPoly { coeffs: eta_coeffs },
+        };
+
+        let (_, eta) = tpv.apply(3.0, 5.0);
+        assert_eq!(eta, 5.0);
+    }
+
+    #[test]
+    fn has_tpv_terms_and_is_tpv_detect_distortion_cards() {
+        let mut s = String::new();
+        s.push_str(&float_card("PV1_0", 0.0));
+        s.push_str(&float_card("PV1_3", 0.01));
+        s.push_str(&pad_card("END".to_string()));
+        let header = WCSHeader::new(&s);
+
+        assert!(has_tpv_terms(&header));
+        assert!(is_tpv(&header, "RA---TAN"));
+        assert!(!is_tpv(&header, "RA---SIN"));
+    }
+
+    #[test]
+    fn has_tpv_terms_is_false_for_a_plain_linear_header() {
+        let mut s = String::new();
+        s.push_str(&float_card("PV1_0", 0.0));
+        s.push_str(&float_card("PV1_1", 1.0));
+        s.push_str(&float_card("PV1_2", 0.0));
+        s.push_str(&pad_card("END".to_string()));
+        let header = WCSHeader::new(&s);
+
+        assert!(!has_tpv_terms(&header));
+        assert!(is_tpv(&header, "RA---TPV"));
+        assert!(!is_tpv(&header, "RA---TAN"));
+    }
+
+    #[test]
+    fn parse_tpv_reads_pv_coefficients_per_axis() {
+        let mut s = String::new();
+        s.push_str(&float_card("PV1_0", 0.1));
+        s.push_str(&float_card("PV1_1", 1.0));
+        s.push_str(&float_card("PV2_0", 0.2));
+        s.push_str(&float_card("PV2_1", 1.0));
+        s.push_str(&pad_card("END".to_string()));
+        let header = WCSHeader::new(&s);
+
+        let tpv = parse_tpv(&header).unwrap();
+        assert_eq!(tpv.xi_poly.coeffs[0], 0.1);
+        assert_eq!(tpv.xi_poly.coeffs[1], 1.0);
+        assert_eq!(tpv.eta_poly.coeffs[0], 0.2);
+        assert_eq!(tpv.eta_poly.coeffs[1], 1.0);
+    }
+
+    /// SIP and TPV describe the same thing (per-axis distortion on top of a
+    /// TAN core) in two incompatible ways, so a header carrying both sets of
+    /// keywords must be rejected rather than silently picking one.
+    #[test]
+    fn parse_tpv_rejects_a_header_that_also_carries_sip_terms() {
+        let mut s = String::new();
+        s.push_str(&int_card("A_ORDER", 2));
+        s.push_str(&pad_card("END".to_string()));
+        let header = WCSHeader::new(&s);
+
+        let err = parse_tpv(&header).unwrap_err();
+        assert!(matches!(err, Error::ConflictingDistortion(_)));
+    }
+}
@@ -13,3 +13,8 @@ pub fn angular_dist(lonlat1: LonLat, lonlat2: LonLat) -> f64 {
         + lonlat1.lat().cos() * lonlat2.lat().cos() * abs_diff_lon.cos())
     .acos()
 }
+
+/// Converts an angle given in arcseconds to radians.
+pub fn arcsec_to_radians(arcsec: f64) -> f64 {
+    arcsec * std::f64::consts::PI / (180.0 * 3600.0)
+}